@@ -8,10 +8,12 @@
 //! 
 //! # Usage
 //! ```rust
-//! use buffered_logger::Logger;
-//! 
+//! use buffered_logger::{Logger, LoggerConfig, Rotation};
+//!
 //! // Initialize the logger and start the service.
-//! let logger = Logger::init(log::Level::Trace, "logs/m.log".to_string(), 10, 1024, 1024 * 5, true).unwrap();
+//! let config = LoggerConfig::new(log::Level::Trace, "logs/m.log".to_string(), 1024, 1024 * 5, 1024 * 1024)
+//!     .rotation(Rotation::Daily);
+//! let logger = Logger::init(config).unwrap();
 //! logger.start();
 //! 
 //! // Now you can start logging.
@@ -30,12 +32,17 @@
 
 use std::{
     fs::{create_dir_all, read_dir, remove_file, rename, File, OpenOptions},
-    io::{stdout, Write},
-    path::{Path, PathBuf},
+    io::{stdout, IsTerminal, Write},
+    path::PathBuf,
     process::exit,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use chrono::{NaiveDateTime, Timelike};
+use crossbeam_channel::{bounded, Receiver, Sender};
 use flate2::{write::GzEncoder, Compression};
 use log::{Level, Metadata, Record, SetLoggerError};
 use permissions::{is_readable, is_writable};
@@ -46,10 +53,502 @@ const LINE_ENDING: &'static str = "\r\n";
 #[cfg(not(windows))]
 const LINE_ENDING: &'static str = "\n";
 
+/// Per-slot cost assumed when sizing the bounded channel from `memory_limit`: the actual
+/// stack size of a queued `Message`, since crossbeam allocates `capacity` slots of this
+/// size upfront regardless of how many are ever filled. A hardcoded guess smaller than
+/// this would under-count and let the channel's own backing array blow past
+/// `memory_limit` before a single message is queued.
+const MIN_MSG_BYTES: usize = std::mem::size_of::<Message>();
+
 enum Message {
     Flush,
     Rotate,
-    Msg(String),
+    Msg {
+        ts: NaiveDateTime,
+        level: Level,
+        target: String,
+        file: Option<String>,
+        line: Option<u32>,
+        body: String,
+    },
+}
+
+/// The pieces of a log record handed to a [`Formatter`], mirroring what `log::Record`
+/// itself exposes.
+pub struct LogRecord<'a> {
+    ts: NaiveDateTime,
+    level: Level,
+    target: &'a str,
+    file: Option<&'a str>,
+    line: Option<u32>,
+    msg: &'a str,
+}
+
+impl<'a> LogRecord<'a> {
+    /// When the record was logged.
+    pub fn ts(&self) -> NaiveDateTime {
+        self.ts
+    }
+
+    /// The record's level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The module path the record was logged from, eg. `my_crate::net`.
+    pub fn target(&self) -> &str {
+        self.target
+    }
+
+    /// The source file the record was logged from, if available.
+    pub fn file(&self) -> Option<&str> {
+        self.file
+    }
+
+    /// The source line the record was logged from, if available.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The formatted message, i.e. `record.args()`.
+    pub fn msg(&self) -> &str {
+        self.msg
+    }
+}
+
+/// Renders a [`LogRecord`] into the bytes a sink buffers and writes to its file.
+///
+/// Implement this to plug in a custom on-disk representation; see [`Plain`] and [`Json`]
+/// for the two built-in formatters.
+pub trait Formatter: Send + Sync {
+    fn format(&self, record: &LogRecord) -> Vec<u8>;
+}
+
+/// The original `[{date} {level}] {msg}` line format.
+pub struct Plain;
+
+impl Formatter for Plain {
+    fn format(&self, record: &LogRecord) -> Vec<u8> {
+        format!(
+            "[{} {}] {}{}",
+            record.ts.format("%F %H:%M:%S%.3f"),
+            record.level,
+            record.msg,
+            LINE_ENDING
+        )
+        .into_bytes()
+    }
+}
+
+/// One JSON object per line: `{"ts":...,"level":...,"target":...,"msg":...}`, plus
+/// `"file"`/`"line"` when the record carries source location.
+pub struct Json;
+
+impl Formatter for Json {
+    fn format(&self, record: &LogRecord) -> Vec<u8> {
+        let mut line = format!(
+            "{{\"ts\":\"{}\",\"level\":\"{}\",\"target\":\"{}\"",
+            record.ts.format("%FT%H:%M:%S%.3f"),
+            record.level,
+            json_escape(record.target),
+        );
+        if let Some(file) = record.file {
+            line.push_str(&format!(",\"file\":\"{}\"", json_escape(file)));
+        }
+        if let Some(l) = record.line {
+            line.push_str(&format!(",\"line\":{}", l));
+        }
+        line.push_str(&format!(",\"msg\":\"{}\"}}{}", json_escape(record.msg), LINE_ENDING));
+        line.into_bytes()
+    }
+}
+
+/// Escapes the characters JSON forbids unescaped inside a string literal.
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// ANSI SGR escape that starts coloring the given level's token; callers must
+/// follow the colored text with [`ANSI_RESET`].
+fn ansi_color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[31m",
+        Level::Warn => "\x1b[33m",
+        Level::Info => "\x1b[32m",
+        Level::Debug => "\x1b[36m",
+        Level::Trace => "\x1b[90m",
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy)]
+/// Controls when `Logger::start`'s background loop rotates the current log file.
+///
+/// Size-based rotation (`rotate_size`) always applies; the variants below additionally
+/// rotate on a calendar boundary computed from the timestamp of each log message.
+pub enum Rotation {
+    /// Rotate only when the current file exceeds `rotate_size` (the original behavior).
+    Size,
+    /// Rotate on `rotate_size`, plus whenever the day changes.
+    Daily,
+    /// Rotate on `rotate_size`, plus whenever the hour changes.
+    Hourly,
+    /// Rotate on `rotate_size`, plus whenever the minute changes.
+    Minutely,
+}
+
+#[derive(Clone, Copy)]
+/// Controls how aggressively `Logger::start`'s background loop fsyncs the log file.
+///
+/// `file.write_all` only hands bytes to the OS page cache, so without an explicit sync a
+/// crash can lose data the caller already considered logged.
+pub enum SyncPolicy {
+    /// Never fsync after writes; rely on the OS to flush the page cache in its own time.
+    /// A rotated file is still synced once before it is archived.
+    Never,
+    /// Call `sync_data` after every `Message::Flush` is handled.
+    EveryFlush,
+    /// Call `sync_data` once at least this many bytes have been written since the last sync.
+    BytesPerSync(usize),
+}
+
+/// Truncates `ts` to the calendar boundary that should trigger a rotation under
+/// `rotation`, or `None` if `rotation` doesn't rotate on a calendar boundary.
+///
+/// The caller caches the returned instant and only re-checks it per message, so this
+/// never needs to format a string to detect a period change.
+fn calendar_bucket(ts: NaiveDateTime, rotation: Rotation) -> Option<NaiveDateTime> {
+    match rotation {
+        Rotation::Size => None,
+        Rotation::Daily => Some(ts.date().and_hms_opt(0, 0, 0).unwrap()),
+        Rotation::Hourly => Some(ts.date().and_hms_opt(ts.hour(), 0, 0).unwrap()),
+        Rotation::Minutely => Some(ts.date().and_hms_opt(ts.hour(), ts.minute(), 0).unwrap()),
+    }
+}
+
+#[derive(Clone)]
+/// Configuration for a secondary sink that additionally receives every record whose
+/// level is at least as severe as `min_level`, alongside the main log file.
+///
+/// Like the main sink, each secondary sink keeps its own buffer, rotation state and
+/// gzip archive of rotated files.
+pub struct Sink {
+    min_level: Level,
+    log_path: String,
+    retain: usize,
+    buffer_size: usize,
+    rotate_size: usize,
+    rotation: Rotation,
+}
+
+impl Sink {
+    /// Create a secondary sink configuration.
+    ///
+    /// # Arguments
+    /// * `min_level` - only records at least this severe are written here. eg. `Level::Warn`.
+    /// * `log_path` - relative or absolute path.
+    /// * `retain` - max number of rotated logs.
+    /// * `buffer_size` - When the size of this sink's buffer becomes higher than this value it will write it to its log file.
+    /// * `rotate_size` - When the size of this sink's current log file becomes higher than this value it will rotate it.
+    /// * `rotation` - calendar rotation policy layered on top of `rotate_size`.
+    pub fn new(
+        min_level: Level,
+        log_path: String,
+        retain: usize,
+        buffer_size: usize,
+        rotate_size: usize,
+        rotation: Rotation,
+    ) -> Sink {
+        Sink {
+            min_level,
+            log_path,
+            retain,
+            buffer_size,
+            rotate_size,
+            rotation,
+        }
+    }
+}
+
+#[derive(Clone)]
+/// A per-target verbosity override: records whose target matches `pattern` use `level`
+/// instead of the logger's global level.
+///
+/// Rules are checked in the order they were given to `Logger::init`; the first match wins,
+/// and a target matching nothing falls back to the global level.
+pub struct TargetFilter {
+    pattern: String,
+    level: Level,
+}
+
+impl TargetFilter {
+    /// Create a target filter rule.
+    ///
+    /// # Arguments
+    /// * `pattern` - a regex matched against `record.target()`, eg. `"^my_crate::net"`.
+    /// * `level` - the level to use for targets matching `pattern`. eg. `Level::Debug`.
+    pub fn new(pattern: String, level: Level) -> TargetFilter {
+        TargetFilter { pattern, level }
+    }
+}
+
+/// Per-sink writer state: the open file, its buffer and its own rotation bookkeeping.
+/// `Logger::start`'s background loop keeps one of these for the main log plus one per
+/// configured `Sink`, so each file rotates and archives independently.
+struct SinkState {
+    log_dir: PathBuf,
+    log_path: PathBuf,
+    file_stem: String,
+    file_ext: String,
+    retain: usize,
+    buffer_size: usize,
+    rotate_size: usize,
+    rotation: Rotation,
+    min_level: Level,
+    file: File,
+    file_size: usize,
+    bytes_buf: Vec<u8>,
+    curr_len: usize,
+    rotated_items: Vec<PathBuf>,
+    curr_period: Option<NaiveDateTime>,
+    bytes_since_sync: usize,
+}
+
+/// Opens `log_path` for a sink, creating its parent directory and scanning for
+/// previously rotated archives, the same way the main log file always has.
+fn open_sink(
+    log_path: String,
+    retain: usize,
+    buffer_size: usize,
+    rotate_size: usize,
+    rotation: Rotation,
+    min_level: Level,
+) -> SinkState {
+    let path = PathBuf::from(&log_path);
+    let log_dir = path.parent().unwrap().to_path_buf();
+    let file_stem = path.file_stem().unwrap().to_str().unwrap().to_string();
+    let file_ext = path.extension().unwrap().to_str().unwrap().to_string();
+
+    match create_dir_all(&log_dir) {
+        Err(err) => {
+            eprintln!(
+                "buffered_logger: Failed to created dir {} - {}",
+                log_dir.to_str().unwrap(),
+                err
+            );
+            exit(-1);
+        }
+        _ => (),
+    }
+    match is_readable(&log_dir) {
+        Ok(readable) => {
+            if !readable {
+                eprintln!(
+                    "buffered_logger: Dir {} is not readable.",
+                    log_dir.to_str().unwrap()
+                );
+                exit(-1);
+            }
+        }
+        Err(err) => {
+            eprintln!(
+                "buffered_logger: Dir {} is not readable - {}",
+                log_dir.to_str().unwrap(),
+                err
+            );
+            exit(-1);
+        }
+    }
+    match is_writable(&log_dir) {
+        Ok(writable) => {
+            if !writable {
+                eprintln!(
+                    "buffered_logger: Dir {} is not writable.",
+                    log_dir.to_str().unwrap()
+                );
+                exit(-1);
+            }
+        }
+        Err(err) => {
+            eprintln!(
+                "buffered_logger: Dir {} is not writable - {}",
+                log_dir.to_str().unwrap(),
+                err
+            );
+            exit(-1);
+        }
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .unwrap();
+    let file_size = file.metadata().unwrap().len() as usize;
+    let curr_period = calendar_bucket(chrono::Local::now().naive_local(), rotation);
+
+    let re = format!(
+        "{}\\.\\d{{6}}\\.\\d{{6}}\\.\\d{{3}}\\.{}\\.gz$",
+        &file_stem, &file_ext
+    );
+    let mut rotated_items: Vec<PathBuf> = read_dir(&log_dir)
+        .unwrap()
+        .filter_map(|res| {
+            let item_path = res.unwrap().path();
+            let re = Regex::new(&re).unwrap();
+            if re.is_match(item_path.to_str().unwrap()) {
+                return Some(item_path);
+            }
+            None
+        })
+        .collect();
+    rotated_items.sort();
+
+    SinkState {
+        log_dir,
+        log_path: path,
+        file_stem,
+        file_ext,
+        retain,
+        buffer_size,
+        rotate_size,
+        rotation,
+        min_level,
+        file,
+        file_size,
+        bytes_buf: vec![0u8; buffer_size],
+        curr_len: 0,
+        rotated_items,
+        curr_period,
+        bytes_since_sync: 0,
+    }
+}
+
+/// Renames `sink`'s current file aside, reopens a fresh one in its place and gzips the
+/// rotated file in the background, trimming old archives down to `sink.retain`.
+fn rotate_sink(sink: &mut SinkState) {
+    // The buffer isn't part of the file on disk yet; without this, whatever was
+    // pending gets flushed into the *next* file instead of the archive being
+    // rotated away, so every rotation would silently drop its tail.
+    if sink.curr_len > 0 {
+        let len = sink.curr_len;
+        sink.file.write_all(&sink.bytes_buf[0..len]).unwrap();
+        sink.curr_len = 0;
+    }
+    sink.file_size = 0;
+
+    let now = chrono::Local::now().naive_local();
+    let rotated_log_base_name = format!(
+        "{}.{}",
+        sink.file_stem,
+        now.format("%y%m%d.%H%M%S%.3f"),
+    );
+    let rotated_log_file_name = format!("{}.{}", rotated_log_base_name, sink.file_ext);
+    let rotated_log_path = sink.log_dir.join(&rotated_log_file_name);
+    rename(&sink.log_path, &rotated_log_path).unwrap();
+    // Flush the renamed file's data out of the page cache before the gzip thread reads
+    // it by path. This only covers bytes already handed to write_all above (including
+    // the buffer flush just above it) — it says nothing about data that was never
+    // written to this file in the first place.
+    sink.file.sync_data().unwrap();
+    sink.bytes_since_sync = 0;
+    sink.file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&sink.log_path)
+        .unwrap();
+    let zip_name = format!("{}.gz", rotated_log_file_name);
+    let zip_path = sink.log_dir.join(zip_name);
+    sink.rotated_items.push(zip_path.clone());
+    while sink.rotated_items.len() > sink.retain {
+        match remove_file(&sink.rotated_items[0]) {
+            _ => (),
+        }
+        sink.rotated_items.remove(0);
+    }
+    std::thread::spawn(move || {
+        let zip_file = std::fs::File::create(zip_path).unwrap();
+        let mut rotated_file = OpenOptions::new()
+            .read(true)
+            .open(&rotated_log_path)
+            .unwrap();
+        let mut zip = GzEncoder::new(&zip_file, Compression::default());
+        std::io::copy(&mut rotated_file, &mut zip).unwrap();
+        zip.flush().unwrap();
+        zip.finish().unwrap();
+        remove_file(rotated_log_path).unwrap();
+    });
+}
+
+/// Fsyncs `sink`'s file once `written` bytes have pushed it over `sync_policy`'s
+/// threshold; a no-op for any other policy.
+fn sync_after_write(sink: &mut SinkState, written: usize, sync_policy: SyncPolicy) {
+    if let SyncPolicy::BytesPerSync(threshold) = sync_policy {
+        sink.bytes_since_sync += written;
+        if sink.bytes_since_sync >= threshold {
+            sink.file.sync_data().unwrap();
+            sink.bytes_since_sync = 0;
+        }
+    }
+}
+
+/// Writes out `sink`'s buffer and applies `sync_policy`.
+fn flush_sink(sink: &mut SinkState, sync_policy: SyncPolicy) {
+    let len = sink.curr_len;
+    sink.file.write_all(&sink.bytes_buf[0..len]).unwrap();
+    if let SyncPolicy::EveryFlush = sync_policy {
+        sink.file.sync_data().unwrap();
+        sink.bytes_since_sync = 0;
+    } else {
+        sync_after_write(sink, len, sync_policy);
+    }
+    sink.curr_len = 0;
+}
+
+/// Rotates `sink` on a calendar boundary or size overflow as needed, then buffers
+/// `data`, flushing the buffer out first if `data` wouldn't otherwise fit.
+fn append_sink(sink: &mut SinkState, ts: NaiveDateTime, data: &[u8], sync_policy: SyncPolicy) {
+    if let Some(period) = calendar_bucket(ts, sink.rotation) {
+        if sink.curr_period.is_some_and(|p| p != period) && sink.file_size > 0 {
+            rotate_sink(sink);
+        }
+        sink.curr_period = Some(period);
+    }
+
+    let len = data.len();
+    let next_file_size = sink.file_size + len;
+    if next_file_size > sink.rotate_size {
+        rotate_sink(sink);
+        sink.file_size = len;
+    } else {
+        sink.file_size = next_file_size;
+    }
+
+    // Computed after the rotation above, since rotate_sink resets curr_len.
+    let next_len = sink.curr_len + len;
+    if next_len > sink.buffer_size {
+        let written = sink.curr_len;
+        sink.file.write_all(&sink.bytes_buf[0..written]).unwrap();
+        sync_after_write(sink, written, sync_policy);
+        sink.bytes_buf[0..len].copy_from_slice(data);
+        sink.curr_len = len;
+    } else {
+        sink.bytes_buf[sink.curr_len..next_len].copy_from_slice(data);
+        sink.curr_len = next_len;
+    }
 }
 
 #[derive(Clone)]
@@ -59,52 +558,204 @@ pub struct Logger {
     retain: usize,
     buffer_size: usize,
     rotate_size: usize,
+    rotation: Rotation,
+    formatter: Arc<dyn Formatter>,
+    sync_policy: SyncPolicy,
+    secondary_sinks: Vec<Sink>,
     stdout: bool,
+    queued_bytes: Arc<AtomicUsize>,
     sender: Sender<Message>,
     receiver: Receiver<Message>,
 }
 
 struct Log {
     level: Level,
+    target_filters: Vec<(Regex, Level)>,
     sender: Sender<Message>,
+    queued_bytes: Arc<AtomicUsize>,
+    dropped: Arc<AtomicUsize>,
+    high_watermark: usize,
+    low_watermark: usize,
+    // Latches shedding on at high_watermark and off at low_watermark, so the two
+    // thresholds actually act as hysteresis instead of shedding re-deriving its
+    // on/off state from the instantaneous queue depth every call (which would
+    // flap every time queued crosses back and forth over high_watermark alone).
+    shedding: AtomicBool,
 }
 
-impl Logger {
-    /// Initialize a logger
+impl Log {
+    /// The level that applies to `target`: the first matching entry in
+    /// `target_filters`, or `self.level` if nothing matches.
+    fn effective_level(&self, target: &str) -> Level {
+        for (pattern, level) in &self.target_filters {
+            if pattern.is_match(target) {
+                return *level;
+            }
+        }
+        self.level
+    }
+}
+
+/// Configuration for [`Logger::init`], built up by chaining setters onto [`LoggerConfig::new`].
+///
+/// `new` takes the arguments with no sensible default; everything else keeps a default
+/// until overridden, which also means `retain` can't be silently transposed with a
+/// same-typed neighbor like `buffer_size` at a positional call site. `buffer_size`,
+/// `rotate_size` and `memory_limit` are still positional arguments of `new`.
+pub struct LoggerConfig {
+    level: Level,
+    log_path: String,
+    buffer_size: usize,
+    rotate_size: usize,
+    memory_limit: usize,
+    target_filters: Vec<TargetFilter>,
+    retain: usize,
+    rotation: Rotation,
+    formatter: Arc<dyn Formatter>,
+    sync_policy: SyncPolicy,
+    secondary_sinks: Vec<Sink>,
+    stdout: bool,
+}
+
+impl LoggerConfig {
+    /// Start a configuration with the arguments that have no sensible default.
     ///
     /// # Arguments
-    /// * `level` - log level. eg. `Level::Info`.
+    /// * `level` - global log level. eg. `Level::Info`.
     /// * `log_path` - relative or absolute path.
-    /// * `retain` - max number of rotated logs.
     /// * `buffer_size` - When the size of log buffer becomes higher than this value it will write it to log file.
     /// * `rotate_size` - When the size of current log file becomes higher than this value it will rotate it.
-    /// * `stdout` - also log to standard output.
+    /// * `memory_limit` - approximate max bytes of log messages queued waiting for the writer thread. Once 90% full, messages below `Level::Warn` are dropped (and a single summary line is emitted once the queue drains back below 80% full) instead of growing without bound.
+    pub fn new(
+        level: Level,
+        log_path: String,
+        buffer_size: usize,
+        rotate_size: usize,
+        memory_limit: usize,
+    ) -> LoggerConfig {
+        LoggerConfig {
+            level,
+            log_path,
+            buffer_size,
+            rotate_size,
+            memory_limit,
+            target_filters: vec![],
+            retain: 10,
+            rotation: Rotation::Size,
+            formatter: Arc::new(Plain),
+            sync_policy: SyncPolicy::Never,
+            secondary_sinks: vec![],
+            stdout: false,
+        }
+    }
+
+    /// Per-target overrides of `level`, checked in order against `record.target()`;
+    /// targets matching none of them use `level`. Defaults to none.
+    pub fn target_filters(mut self, target_filters: Vec<TargetFilter>) -> LoggerConfig {
+        self.target_filters = target_filters;
+        self
+    }
+
+    /// Max number of rotated logs to keep. Defaults to `10`.
+    pub fn retain(mut self, retain: usize) -> LoggerConfig {
+        self.retain = retain;
+        self
+    }
+
+    /// Calendar rotation policy layered on top of `rotate_size`. Defaults to `Rotation::Size`.
+    pub fn rotation(mut self, rotation: Rotation) -> LoggerConfig {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Renders each record into the bytes written to every sink. Defaults to `Plain`.
+    pub fn formatter(mut self, formatter: Arc<dyn Formatter>) -> LoggerConfig {
+        self.formatter = formatter;
+        self
+    }
+
+    /// How often the log file is fsynced. Defaults to `SyncPolicy::Never`.
+    pub fn sync_policy(mut self, sync_policy: SyncPolicy) -> LoggerConfig {
+        self.sync_policy = sync_policy;
+        self
+    }
+
+    /// Additional sinks that mirror records at or above their own `min_level` into their
+    /// own file, eg. a dedicated error log next to the main one. Defaults to none.
+    pub fn secondary_sinks(mut self, secondary_sinks: Vec<Sink>) -> LoggerConfig {
+        self.secondary_sinks = secondary_sinks;
+        self
+    }
+
+    /// Also log to standard output. Defaults to `false`.
+    pub fn stdout(mut self, stdout: bool) -> LoggerConfig {
+        self.stdout = stdout;
+        self
+    }
+}
+
+impl Logger {
+    /// Initialize a logger from a [`LoggerConfig`].
     ///
     /// # Example
     /// ```rust
-    /// use buffered_logger::Logger;
+    /// use buffered_logger::{Logger, LoggerConfig, Plain, Rotation, Sink, SyncPolicy};
+    /// use std::sync::Arc;
     ///
-    /// let logger = Logger::init(log::Level::Trace, "logs/m.log".to_string(), 10, 1024, 1024 * 5, true).unwrap();
+    /// let error_log = Sink::new(log::Level::Warn, "logs/errors.log".to_string(), 10, 1024, 1024 * 5, Rotation::Daily);
+    /// let config = LoggerConfig::new(log::Level::Trace, "logs/m.log".to_string(), 1024, 1024 * 5, 1024 * 1024)
+    ///     .rotation(Rotation::Daily)
+    ///     .formatter(Arc::new(Plain))
+    ///     .sync_policy(SyncPolicy::BytesPerSync(1024 * 1024))
+    ///     .secondary_sinks(vec![error_log])
+    ///     .stdout(true);
+    /// let logger = Logger::init(config).unwrap();
     /// ```
-    pub fn init(
-        level: Level,
-        log_path: String,
-        retain: usize,
-        buffer_size: usize,
-        rotate_size: usize,
-        stdout: bool,
-    ) -> Result<Logger, SetLoggerError> {
-        let (sender, receiver) = unbounded();
-        let lf = match level {
+    pub fn init(config: LoggerConfig) -> Result<Logger, SetLoggerError> {
+        let LoggerConfig {
+            level,
+            log_path,
+            buffer_size,
+            rotate_size,
+            memory_limit,
+            target_filters,
+            retain,
+            rotation,
+            formatter,
+            sync_policy,
+            secondary_sinks,
+            stdout,
+        } = config;
+        let capacity = (memory_limit / MIN_MSG_BYTES).max(16);
+        let (sender, receiver) = bounded(capacity);
+        let target_filters: Vec<(Regex, Level)> = target_filters
+            .into_iter()
+            .map(|f| (Regex::new(&f.pattern).unwrap(), f.level))
+            .collect();
+        // The global max level must admit the most verbose level any rule asks for,
+        // or the `log` crate's own callsite filter would drop those records before
+        // they ever reach `Log::log`.
+        let max_verbosity = target_filters
+            .iter()
+            .map(|(_, l)| *l)
+            .fold(level, |acc, l| if l > acc { l } else { acc });
+        let lf = match max_verbosity {
             Level::Trace => log::LevelFilter::Trace,
             Level::Debug => log::LevelFilter::Debug,
             Level::Info => log::LevelFilter::Info,
             Level::Warn => log::LevelFilter::Warn,
             Level::Error => log::LevelFilter::Error,
         };
+        let queued_bytes = Arc::new(AtomicUsize::new(0));
         log::set_boxed_logger(Box::new(Log {
             level,
+            target_filters,
             sender: sender.clone(),
+            queued_bytes: queued_bytes.clone(),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            high_watermark: memory_limit * 9 / 10,
+            low_watermark: memory_limit * 8 / 10,
+            shedding: AtomicBool::new(false),
         }))
         .map(|()| log::set_max_level(lf))?;
         Ok(Logger {
@@ -112,7 +763,12 @@ impl Logger {
             retain,
             buffer_size,
             rotate_size,
+            rotation,
+            formatter,
+            sync_policy,
+            secondary_sinks,
             stdout,
+            queued_bytes,
             sender,
             receiver,
         })
@@ -122,160 +778,85 @@ impl Logger {
     pub fn start(&self) {
         let this = self.clone();
         std::thread::spawn(move || {
-            let mut curr_len: usize = 0;
-            let mut bytes_buf = vec![0u8; this.buffer_size];
-            let log_path = Path::new(this.log_path.as_str());
-            let log_dir = log_path.parent().unwrap();
-            let file_stem = log_path.file_stem().unwrap().to_str().unwrap();
-            let file_ext = log_path.extension().unwrap().to_str().unwrap();
-
-            match create_dir_all(&log_dir) {
-                Err(err) => {
-                    eprintln!(
-                        "buffered_logger: Failed to created dir {} - {}",
-                        log_dir.to_str().unwrap(),
-                        err
-                    );
-                    exit(-1);
-                }
-                _ => (),
-            }
-            match is_readable(&log_dir) {
-                Ok(readable) => {
-                    if !readable {
-                        eprintln!(
-                            "buffered_logger: Dir {} is not readable.",
-                            log_dir.to_str().unwrap()
-                        );
-                        exit(-1);
-                    }
-                }
-                Err(err) => {
-                    eprintln!(
-                        "buffered_logger: Dir {} is not readable - {}",
-                        log_dir.to_str().unwrap(),
-                        err
-                    );
-                    exit(-1);
-                }
-            }
-            match is_writable(&log_dir) {
-                Ok(writable) => {
-                    if !writable {
-                        eprintln!(
-                            "buffered_logger: Dir {} is not writable.",
-                            log_dir.to_str().unwrap()
-                        );
-                        exit(-1);
-                    }
-                }
-                Err(err) => {
-                    eprintln!(
-                        "buffered_logger: Dir {} is not writable - {}",
-                        log_dir.to_str().unwrap(),
-                        err
-                    );
-                    exit(-1);
-                }
-            }
-
-            let mut file = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(log_path)
-                .unwrap();
-            let mut file_size = file.metadata().unwrap().len() as usize;
-
-            let re = format!(
-                "{}\\.\\d{{6}}\\.\\d{{6}}\\.\\d{{3}}\\.{}\\.gz$",
-                &file_stem, &file_ext
+            let mut main_sink = open_sink(
+                this.log_path.clone(),
+                this.retain,
+                this.buffer_size,
+                this.rotate_size,
+                this.rotation,
+                Level::Trace,
             );
-            let mut rotated_items: Vec<PathBuf> = read_dir(&log_dir)
-                .unwrap()
-                .filter_map(|res| {
-                    let path = res.unwrap().path();
-                    let re = Regex::new(&re).unwrap();
-                    if re.is_match(path.to_str().unwrap()) {
-                        return Some(path);
-                    }
-                    None
+            let mut secondary_sinks: Vec<SinkState> = this
+                .secondary_sinks
+                .iter()
+                .map(|s| {
+                    open_sink(
+                        s.log_path.clone(),
+                        s.retain,
+                        s.buffer_size,
+                        s.rotate_size,
+                        s.rotation,
+                        s.min_level,
+                    )
                 })
                 .collect();
-            rotated_items.sort();
-
-            let retain = this.retain;
-            let rotate =
-                |rotated_items: &mut Vec<PathBuf>, file: &mut File, file_size: &mut usize| {
-                    *file_size = 0;
-
-                    let now = chrono::Local::now().naive_local();
-                    let rotated_log_base_name =
-                        format!("{}.{}", file_stem, now.format("%y%m%d.%H%M%S%.3f"),);
-                    let rotated_log_file_name = format!("{}.{}", rotated_log_base_name, file_ext);
-                    let rotated_log_path = log_dir.join(&rotated_log_file_name);
-                    rename(log_path, &rotated_log_path).unwrap();
-                    *file = OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(log_path)
-                        .unwrap();
-                    let zip_name = format!("{}.gz", rotated_log_file_name);
-                    let zip_path = log_dir.join(zip_name);
-                    rotated_items.push(zip_path.clone());
-                    while rotated_items.len() > retain {
-                        match remove_file(&rotated_items[0]) {
-                            _ => (),
-                        }
-                        rotated_items.remove(0);
-                    }
-                    std::thread::spawn(move || {
-                        let zip_file = std::fs::File::create(zip_path).unwrap();
-                        let mut rotated_file = OpenOptions::new()
-                            .read(true)
-                            .open(&rotated_log_path)
-                            .unwrap();
-                        let mut zip = GzEncoder::new(&zip_file, Compression::default());
-                        std::io::copy(&mut rotated_file, &mut zip).unwrap();
-                        zip.flush().unwrap();
-                        zip.finish().unwrap();
-                        remove_file(rotated_log_path).unwrap();
-                    });
-                };
+            let ansi = this.stdout && stdout().is_terminal();
 
             loop {
                 let data = this.receiver.recv().unwrap();
                 match data {
                     Message::Flush => {
-                        let s = &bytes_buf[0..curr_len];
-                        file.write_all(s).unwrap();
-                        if this.stdout {
-                            stdout().write_all(s).unwrap();
+                        flush_sink(&mut main_sink, this.sync_policy);
+                        for sink in secondary_sinks.iter_mut() {
+                            flush_sink(sink, this.sync_policy);
                         }
-                        curr_len = 0;
                     }
                     Message::Rotate => {
-                        rotate(&mut rotated_items, &mut file, &mut file_size);
+                        rotate_sink(&mut main_sink);
+                        for sink in secondary_sinks.iter_mut() {
+                            rotate_sink(sink);
+                        }
                     }
-                    Message::Msg(data) => {
-                        let len = data.len();
-                        let next_len = curr_len + len;
-                        let next_file_size = file_size + len;
-                        if next_file_size > this.rotate_size {
-                            rotate(&mut rotated_items, &mut file, &mut file_size);
-                        } else {
-                            file_size = next_file_size;
+                    Message::Msg {
+                        ts,
+                        level,
+                        target,
+                        file,
+                        line,
+                        body,
+                    } => {
+                        this.queued_bytes.fetch_sub(body.len(), Ordering::Relaxed);
+                        let ts_str = ts.format("%F %H:%M:%S%.3f");
+                        if this.stdout {
+                            let stdout_line = if ansi {
+                                format!(
+                                    "[{} {}{}{}] {}{}",
+                                    ts_str,
+                                    ansi_color(level),
+                                    level,
+                                    ANSI_RESET,
+                                    body,
+                                    LINE_ENDING
+                                )
+                            } else {
+                                format!("[{} {}] {}{}", ts_str, level, body, LINE_ENDING)
+                            };
+                            stdout().write_all(stdout_line.as_bytes()).unwrap();
                         }
-                        if next_len > this.buffer_size {
-                            let s = &bytes_buf[0..curr_len];
-                            file.write_all(s).unwrap();
-                            if this.stdout {
-                                stdout().write_all(s).unwrap();
+                        let record = LogRecord {
+                            ts,
+                            level,
+                            target: &target,
+                            file: file.as_deref(),
+                            line,
+                            msg: &body,
+                        };
+                        let data = this.formatter.format(&record);
+                        append_sink(&mut main_sink, ts, &data, this.sync_policy);
+                        for sink in secondary_sinks.iter_mut() {
+                            if level <= sink.min_level {
+                                append_sink(sink, ts, &data, this.sync_policy);
                             }
-                            bytes_buf[0..len].copy_from_slice(data.as_bytes());
-                            curr_len = len;
-                        } else {
-                            bytes_buf[curr_len..next_len].copy_from_slice(data.as_bytes());
-                            curr_len = next_len;
                         }
                     }
                 }
@@ -296,20 +877,63 @@ impl Logger {
 
 impl log::Log for Log {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level
+        metadata.level() <= self.effective_level(metadata.target())
     }
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
+            let level = record.level();
+            let queued = self.queued_bytes.load(Ordering::Relaxed);
+
+            // Cross high_watermark to start shedding, low_watermark to stop; queued
+            // sitting anywhere between the two leaves whichever state we were
+            // already in untouched, which is what makes this hysteresis instead of
+            // flapping on and off around a single threshold.
+            if queued >= self.high_watermark {
+                self.shedding.store(true, Ordering::Relaxed);
+            } else if queued <= self.low_watermark {
+                self.shedding.store(false, Ordering::Relaxed);
+            }
+
+            // While shedding: drop everything less severe than a warning rather than
+            // let the queue grow without bound ahead of a slow disk.
+            if self.shedding.load(Ordering::Relaxed) && level > Level::Warn {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+
             let now = chrono::Local::now().naive_local();
+
+            // No longer shedding: report what was shed, once.
+            if !self.shedding.load(Ordering::Relaxed) && self.dropped.load(Ordering::Relaxed) > 0 {
+                let n = self.dropped.swap(0, Ordering::Relaxed);
+                if n > 0 {
+                    let summary = format!("{} messages dropped", n);
+                    self.queued_bytes.fetch_add(summary.len(), Ordering::Relaxed);
+                    self.sender
+                        .send(Message::Msg {
+                            ts: now,
+                            level: Level::Warn,
+                            target: record.target().to_string(),
+                            file: None,
+                            line: None,
+                            body: summary,
+                        })
+                        .unwrap();
+                }
+            }
+
+            let body = format!("{}", record.args());
+            self.queued_bytes.fetch_add(body.len(), Ordering::Relaxed);
             self.sender
-                .send(Message::Msg(format!(
-                    "[{} {}] {}{}",
-                    now.format("%F %H:%M:%S%.3f"),
-                    record.level(),
-                    record.args(),
-                    LINE_ENDING
-                )))
+                .send(Message::Msg {
+                    ts: now,
+                    level,
+                    target: record.target().to_string(),
+                    file: record.file().map(String::from),
+                    line: record.line(),
+                    body,
+                })
                 .unwrap();
         }
     }
@@ -318,3 +942,109 @@ impl log::Log for Log {
         self.sender.send(Message::Flush).unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    // Exercises rotate_sink via size-based rotation rather than a calendar boundary,
+    // since open_sink/rotate_sink always read the wall clock and there's no injected
+    // clock to fake a boundary crossing with; both paths rotate through the same
+    // rotate_sink, so this covers the bug either would have hit.
+    #[test]
+    fn rotate_sink_archives_buffered_bytes_instead_of_dropping_them() {
+        let dir = std::env::temp_dir().join(format!(
+            "buffered_logger_test_{}",
+            std::process::id()
+        ));
+        create_dir_all(&dir).unwrap();
+        let log_path = dir.join("test.log").to_str().unwrap().to_string();
+
+        let mut sink = open_sink(log_path, 10, 1024, 16, Rotation::Size, Level::Trace);
+        let ts = chrono::Local::now().naive_local();
+
+        // Sits only in bytes_buf; nothing has reached the file yet.
+        append_sink(&mut sink, ts, b"first-msg;", SyncPolicy::Never);
+        assert_eq!(sink.curr_len, 10);
+
+        // file_size (10) + this message's len (11) exceeds rotate_size (16), so this
+        // triggers rotate_sink, which must flush "first-msg;" into the archive before
+        // renaming it away rather than letting it leak into the new file.
+        append_sink(&mut sink, ts, b"second-msg;", SyncPolicy::Never);
+        assert_eq!(sink.curr_len, 11);
+
+        // Give the background gzip thread a moment to finish archiving.
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        let archive = sink.rotated_items.last().unwrap().clone();
+        let mut gz = flate2::read::GzDecoder::new(File::open(&archive).unwrap());
+        let mut archived_bytes = Vec::new();
+        gz.read_to_end(&mut archived_bytes).unwrap();
+        assert_eq!(archived_bytes, b"first-msg;");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn json_formatter_escapes_quotes_and_control_characters() {
+        let ts = chrono::NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_milli_opt(3, 4, 5, 678)
+            .unwrap();
+        let record = LogRecord {
+            ts,
+            level: Level::Info,
+            target: "my_crate::net",
+            file: Some("src/net.rs"),
+            line: Some(42),
+            msg: "say \"hi\"\tthen\nbye",
+        };
+
+        let out = String::from_utf8(Json.format(&record)).unwrap();
+        let (json_part, ending) = out.split_at(out.len() - LINE_ENDING.len());
+
+        assert_eq!(ending, LINE_ENDING);
+        assert!(json_part.starts_with(&format!(
+            "{{\"ts\":\"{}\",\"level\":\"{}\",\"target\":\"my_crate::net\"",
+            ts.format("%FT%H:%M:%S%.3f"),
+            Level::Info,
+        )));
+        assert!(json_part.contains("\"file\":\"src/net.rs\""));
+        assert!(json_part.contains("\"line\":42"));
+        // The raw quote, tab and newline must not survive unescaped...
+        assert!(!json_part.contains('\t'));
+        assert!(!json_part.contains('\n'));
+        // ...but their escaped forms must be present.
+        assert!(json_part.contains("\\\"hi\\\""));
+        assert!(json_part.contains("\\t"));
+        assert!(json_part.contains("\\n"));
+        assert!(json_part.ends_with('}'));
+    }
+
+    #[test]
+    fn effective_level_honors_first_matching_rule_then_falls_back_to_global() {
+        let (sender, _receiver) = bounded(1);
+        let log = Log {
+            level: Level::Info,
+            target_filters: vec![
+                (Regex::new("^my_crate::net").unwrap(), Level::Debug),
+                (Regex::new("^my_crate").unwrap(), Level::Warn),
+            ],
+            sender,
+            queued_bytes: Arc::new(AtomicUsize::new(0)),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            high_watermark: usize::MAX,
+            low_watermark: 0,
+            shedding: AtomicBool::new(false),
+        };
+
+        // Matches the first, more specific rule even though the second, broader
+        // rule would also match — first match wins, not most specific match.
+        assert_eq!(log.effective_level("my_crate::net::socket"), Level::Debug);
+        // Matches only the second rule.
+        assert_eq!(log.effective_level("my_crate::db"), Level::Warn);
+        // Matches no rule: falls back to the global level.
+        assert_eq!(log.effective_level("other_crate"), Level::Info);
+    }
+}
@@ -1,15 +1,32 @@
-use buffered_logger::Logger;
+use buffered_logger::{Logger, LoggerConfig, Plain, Rotation, Sink, SyncPolicy, TargetFilter};
+use std::sync::Arc;
 
 fn main() {
-    let logger = Logger::init(
+    let error_log = Sink::new(
+        log::Level::Warn,
+        "logs/errors.log".to_string(),
+        10,
+        1024,
+        1024 * 5,
+        Rotation::Daily,
+    );
+    let config = LoggerConfig::new(
         log::Level::Info,
         "logs/m.log".to_string(),
-        10,
         1024,
         1024 * 5,
-        true,
+        1024 * 1024,
     )
-    .unwrap();
+    .target_filters(vec![TargetFilter::new(
+        "^buffered_logger".to_string(),
+        log::Level::Debug,
+    )])
+    .rotation(Rotation::Daily)
+    .formatter(Arc::new(Plain))
+    .sync_policy(SyncPolicy::BytesPerSync(1024 * 1024))
+    .secondary_sinks(vec![error_log])
+    .stdout(true);
+    let logger = Logger::init(config).unwrap();
     logger.start();
     log::info!("started");
     logger.flush();